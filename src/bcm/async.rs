@@ -1,5 +1,5 @@
-use libc::{c_int, c_short, c_void, c_uint, socket, fcntl, close, connect, sockaddr, read, write,
-           timeval, F_SETFL, O_NONBLOCK};
+use libc::{c_int, c_short, c_void, c_uint, socket, fcntl, close, connect, sockaddr, read, recv,
+           write, timeval, F_SETFL, O_NONBLOCK, MSG_PEEK};
 
 use futures;
 use mio::{Evented, Ready, Poll, PollOpt, Token};
@@ -16,6 +16,52 @@ use {CanAddr, CanFrame, CanSocketOpenError, AF_CAN, EFF_FLAG, PF_CAN, SOCK_DGRAM
 
 pub const MAX_NFRAMES: u32 = 256;
 
+/// Maximum payload length of a CAN FD frame, per the kernel's
+/// `CANFD_MAX_DLEN`.
+pub const CANFD_MAX_DLEN: usize = 64;
+
+/// A CAN FD frame, mirroring the kernel's `struct canfd_frame`. Unlike
+/// classic `CanFrame`s, FD frames carry up to `CANFD_MAX_DLEN` bytes of data
+/// and no RTR bit.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct CanFdFrame {
+    _can_id: u32,
+    _len: u8,
+    _flags: u8,
+    _res0: u8,
+    _res1: u8,
+    _data: [u8; CANFD_MAX_DLEN],
+}
+
+impl CanFdFrame {
+    pub fn new(can_id: u32, data: &[u8], eff: bool) -> Option<CanFdFrame> {
+        if data.len() > CANFD_MAX_DLEN {
+            return None;
+        }
+
+        let mut _data = [0u8; CANFD_MAX_DLEN];
+        _data[..data.len()].copy_from_slice(data);
+
+        Some(CanFdFrame {
+            _can_id: if eff { can_id | EFF_FLAG } else { can_id },
+            _len: data.len() as u8,
+            _flags: 0,
+            _res0: 0,
+            _res1: 0,
+            _data: _data,
+        })
+    }
+
+    pub fn can_id(&self) -> u32 {
+        self._can_id
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self._data[..self._len as usize]
+    }
+}
+
 /// OpCodes
 ///
 /// create (cyclic) transmission task
@@ -132,6 +178,116 @@ pub struct TxMsg {
     _frames: [CanFrame; MAX_NFRAMES as usize],
 }
 
+/// BcmMsgHeadFd
+///
+/// FD-aware equivalent of `BcmMsgHead`: same head layout, but the appended
+/// frame buffer holds `CanFdFrame`s. The kernel tells these two apart via
+/// the `CAN_FD_FRAME` bit in `_flags`.
+#[repr(C)]
+pub struct BcmMsgHeadFd {
+    _opcode: u32,
+    _flags: u32,
+    _count: u32,
+    _ival1: timeval,
+    _ival2: timeval,
+    _can_id: u32,
+    _nframes: u32,
+    #[cfg(all(target_pointer_width = "32"))]
+    _pad: u32,
+    _frames: [CanFdFrame; MAX_NFRAMES as usize],
+}
+
+impl BcmMsgHeadFd {
+    pub fn can_id(&self) -> u32 {
+        self._can_id
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[CanFdFrame] {
+        return unsafe { slice::from_raw_parts(self._frames.as_ptr(), self._nframes as usize) };
+    }
+
+    pub fn opcode(&self) -> u32 {
+        self._opcode
+    }
+
+    pub fn flags(&self) -> u32 {
+        self._flags
+    }
+
+    pub fn count(&self) -> u32 {
+        self._count
+    }
+
+    pub fn ival1(&self) -> time::Duration {
+        duration_from_timeval(self._ival1)
+    }
+
+    pub fn ival2(&self) -> time::Duration {
+        duration_from_timeval(self._ival2)
+    }
+
+    pub fn kind(&self) -> BcmOpcode {
+        BcmOpcode::from_raw(self._opcode)
+    }
+}
+
+#[repr(C)]
+pub struct TxMsgFd {
+    _msg_head: BcmMsgHeadFrameLess,
+    _frames: [CanFdFrame; MAX_NFRAMES as usize],
+}
+
+/// Pad `frames` out to a fixed `[CanFdFrame; MAX_NFRAMES]` buffer, mirroring
+/// `padded_frames` for the classic-frame path.
+fn padded_fd_frames(frames: &[CanFdFrame]) -> io::Result<[CanFdFrame; MAX_NFRAMES as usize]> {
+    if frames.len() > MAX_NFRAMES as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "too many frames for a single BCM message",
+        ));
+    }
+
+    let mut buf = [CanFdFrame::new(0x0, &[], false).unwrap(); MAX_NFRAMES as usize];
+    buf[..frames.len()].copy_from_slice(frames);
+    Ok(buf)
+}
+
+/// Identifies the kind of notification a `BcmMsgHead` carries, decoded from
+/// its `_opcode` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BcmOpcode {
+    /// Reply to a `TX_READ` request.
+    TxStatus,
+    /// Notification that a cyclic transmission's `count` has expired.
+    TxExpired,
+    /// Reply to an `RX_READ` request.
+    RxStatus,
+    /// A subscribed cyclic message failed to arrive within its timeout.
+    RxTimeout,
+    /// The first or a revised CAN message matching a content filter arrived.
+    RxChanged,
+    /// An opcode this crate doesn't decode into a dedicated variant.
+    Other(u32),
+}
+
+impl BcmOpcode {
+    fn from_raw(opcode: u32) -> BcmOpcode {
+        match opcode {
+            TX_STATUS => BcmOpcode::TxStatus,
+            TX_EXPIRED => BcmOpcode::TxExpired,
+            RX_STATUS => BcmOpcode::RxStatus,
+            RX_TIMEOUT => BcmOpcode::RxTimeout,
+            RX_CHANGED => BcmOpcode::RxChanged,
+            other => BcmOpcode::Other(other),
+        }
+    }
+}
+
+fn duration_from_timeval(tv: timeval) -> time::Duration {
+    time::Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000)
+}
+
 impl BcmMsgHead {
     pub fn can_id(&self) -> u32 {
         self._can_id
@@ -141,6 +297,60 @@ impl BcmMsgHead {
     pub fn frames(&self) -> &[CanFrame] {
         return unsafe { slice::from_raw_parts(self._frames.as_ptr(), self._nframes as usize) };
     }
+
+    /// The raw opcode the kernel tagged this message with. See `kind()` for
+    /// a decoded form.
+    pub fn opcode(&self) -> u32 {
+        self._opcode
+    }
+
+    pub fn flags(&self) -> u32 {
+        self._flags
+    }
+
+    /// Remaining count of leading-interval messages (for `TX_STATUS`), or the
+    /// `count` that was passed to `send_cyclic`.
+    pub fn count(&self) -> u32 {
+        self._count
+    }
+
+    pub fn ival1(&self) -> time::Duration {
+        duration_from_timeval(self._ival1)
+    }
+
+    pub fn ival2(&self) -> time::Duration {
+        duration_from_timeval(self._ival2)
+    }
+
+    /// Decodes `opcode()` into a `BcmOpcode`, so callers can tell an
+    /// `RX_CHANGED` from an `RX_TIMEOUT`, `TX_EXPIRED` or `RX_STATUS` without
+    /// matching on the raw constants.
+    pub fn kind(&self) -> BcmOpcode {
+        BcmOpcode::from_raw(self._opcode)
+    }
+}
+
+/// Pad `frames` out to a fixed `[CanFrame; MAX_NFRAMES]` buffer suitable for
+/// embedding in a `TxMsg`, erroring out if more frames are supplied than the
+/// broadcast manager can hold in a single message.
+fn padded_frames(frames: &[CanFrame]) -> io::Result<[CanFrame; MAX_NFRAMES as usize]> {
+    if frames.len() > MAX_NFRAMES as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "too many frames for a single BCM message",
+        ));
+    }
+
+    let mut buf = [CanFrame::new(0x0, &[], false, false).unwrap(); MAX_NFRAMES as usize];
+    buf[..frames.len()].copy_from_slice(frames);
+    Ok(buf)
+}
+
+/// Build the `_can_id` field for a `BcmMsgHead`-like struct, tagging it as an
+/// extended (29-bit) id only when the caller asks for it, rather than always
+/// forcing `EFF_FLAG` on a standard (11-bit) id.
+fn eff_can_id(can_id: c_uint, eff: bool) -> u32 {
+    if eff { can_id as u32 | EFF_FLAG } else { can_id as u32 }
 }
 
 /// A socket for a CAN device, specifically for broadcast manager operations.
@@ -255,6 +465,59 @@ impl CanBCMSocket {
         Ok(())
     }
 
+    /// Create a content-change RX filter subscription.
+    ///
+    /// Unlike `filter_id`, which fires on every matching frame, this passes
+    /// `masks` as the RX_SETUP frame buffer so the kernel only emits
+    /// `RX_CHANGED` when the masked data bytes differ from the last seen
+    /// value. With a single mask frame `masks[0]`'s data forms the relevance
+    /// bitmask; with more than one, `masks[0]` is the mask and the remaining
+    /// frames enumerate the multiplex values to track independently. Pass
+    /// `RX_CHECK_DLC`, `RX_ANNOUNCE_RESUM` and/or `RX_NO_AUTOTIMER` in `flags`
+    /// as needed. Set `eff` to tag `can_id` as an extended (29-bit) id;
+    /// leave it unset for a standard (11-bit) id.
+    pub fn filter_content(
+        &self,
+        can_id: c_uint,
+        eff: bool,
+        masks: &[CanFrame],
+        ival1: time::Duration,
+        ival2: time::Duration,
+        flags: u32,
+    ) -> io::Result<()> {
+        let nframes = masks.len();
+        let frame_buf = padded_frames(masks)?;
+
+        let msg = BcmMsgHeadFrameLess {
+            _opcode: RX_SETUP,
+            _flags: SETTIMER | flags,
+            _count: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(ival1),
+            _ival2: c_timeval_new(ival2),
+            _can_id: eff_can_id(can_id, eff),
+            _nframes: nframes as u32,
+        };
+
+        let tx_msg = &TxMsg {
+            _msg_head: msg,
+            _frames: frame_buf,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>() + nframes * size_of::<CanFrame>();
+        let write_rv = unsafe {
+            let tx_msg_ptr = tx_msg as *const TxMsg;
+            write(self.fd, tx_msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
     /// Remove a content filter subscription.
     pub fn filter_delete(&self, can_id: c_uint) -> io::Result<()> {
         let frames = [CanFrame::new(0x0, &[], false, false).unwrap(); MAX_NFRAMES as usize];
@@ -286,6 +549,372 @@ impl CanBCMSocket {
         Ok(())
     }
 
+    /// Create or replace a cyclic transmission task.
+    ///
+    /// The broadcast manager sends the leading `count` messages spaced by
+    /// `ival1`, then continues indefinitely spaced by `ival2`. If `count` is
+    /// zero only `ival2` applies. When `frames` holds more than one frame the
+    /// task cycles through them in order on every tick. The task starts
+    /// immediately; pass `TX_CP_CAN_ID` in `flags` to have the kernel stamp
+    /// `can_id` onto each frame in the buffer. Set `eff` to tag `can_id` as
+    /// an extended (29-bit) id; leave it unset for a standard (11-bit) id.
+    pub fn send_cyclic(
+        &self,
+        can_id: c_uint,
+        eff: bool,
+        frames: &[CanFrame],
+        count: u32,
+        ival1: time::Duration,
+        ival2: time::Duration,
+        flags: u32,
+    ) -> io::Result<()> {
+        let nframes = frames.len();
+        let frame_buf = padded_frames(frames)?;
+
+        let msg = BcmMsgHeadFrameLess {
+            _opcode: TX_SETUP,
+            _flags: SETTIMER | STARTTIMER | flags,
+            _count: count,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(ival1),
+            _ival2: c_timeval_new(ival2),
+            _can_id: eff_can_id(can_id, eff),
+            _nframes: nframes as u32,
+        };
+
+        let tx_msg = &TxMsg {
+            _msg_head: msg,
+            _frames: frame_buf,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>() + nframes * size_of::<CanFrame>();
+        let write_rv = unsafe {
+            let tx_msg_ptr = tx_msg as *const TxMsg;
+            write(self.fd, tx_msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    /// Remove a cyclic transmission task previously set up with `send_cyclic`
+    /// or `send_cyclic_fd`. `eff` must match what was passed at setup for
+    /// this `can_id`; set `fd` to remove a task set up with `send_cyclic_fd`.
+    pub fn send_cyclic_delete(&self, can_id: c_uint, eff: bool, fd: bool) -> io::Result<()> {
+        let msg = &BcmMsgHeadFrameLess {
+            _opcode: TX_DELETE,
+            _flags: if fd { CAN_FD_FRAME } else { 0 },
+            _count: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(time::Duration::new(0, 0)),
+            _ival2: c_timeval_new(time::Duration::new(0, 0)),
+            _can_id: eff_can_id(can_id, eff),
+            _nframes: 0,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>();
+        let write_rv = unsafe {
+            let msg_ptr = msg as *const BcmMsgHeadFrameLess;
+            write(self.fd, msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    /// FD-aware equivalent of `send_cyclic`, for buses running CAN FD.
+    /// Behaves identically but carries up to `CANFD_MAX_DLEN`-byte payloads
+    /// and sets `CAN_FD_FRAME` on the task so the kernel dispatches it as an
+    /// FD task. Remove it with `send_cyclic_delete`, passing `fd`. Set `eff` to
+    /// tag `can_id` as an extended (29-bit) id; leave it unset for a
+    /// standard (11-bit) id.
+    pub fn send_cyclic_fd(
+        &self,
+        can_id: c_uint,
+        eff: bool,
+        frames: &[CanFdFrame],
+        count: u32,
+        ival1: time::Duration,
+        ival2: time::Duration,
+        flags: u32,
+    ) -> io::Result<()> {
+        let nframes = frames.len();
+        let frame_buf = padded_fd_frames(frames)?;
+
+        let msg = BcmMsgHeadFrameLess {
+            _opcode: TX_SETUP,
+            _flags: SETTIMER | STARTTIMER | CAN_FD_FRAME | flags,
+            _count: count,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(ival1),
+            _ival2: c_timeval_new(ival2),
+            _can_id: eff_can_id(can_id, eff),
+            _nframes: nframes as u32,
+        };
+
+        let tx_msg = &TxMsgFd {
+            _msg_head: msg,
+            _frames: frame_buf,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>() + nframes * size_of::<CanFdFrame>();
+        let write_rv = unsafe {
+            let tx_msg_ptr = tx_msg as *const TxMsgFd;
+            write(self.fd, tx_msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    /// FD-aware equivalent of `filter_content`, for CAN FD masks/multiplex
+    /// values up to `CANFD_MAX_DLEN` bytes. Set `eff` to tag `can_id` as an
+    /// extended (29-bit) id; leave it unset for a standard (11-bit) id.
+    pub fn filter_content_fd(
+        &self,
+        can_id: c_uint,
+        eff: bool,
+        masks: &[CanFdFrame],
+        ival1: time::Duration,
+        ival2: time::Duration,
+        flags: u32,
+    ) -> io::Result<()> {
+        let nframes = masks.len();
+        let frame_buf = padded_fd_frames(masks)?;
+
+        let msg = BcmMsgHeadFrameLess {
+            _opcode: RX_SETUP,
+            _flags: SETTIMER | CAN_FD_FRAME | flags,
+            _count: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(ival1),
+            _ival2: c_timeval_new(ival2),
+            _can_id: eff_can_id(can_id, eff),
+            _nframes: nframes as u32,
+        };
+
+        let tx_msg = &TxMsgFd {
+            _msg_head: msg,
+            _frames: frame_buf,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>() + nframes * size_of::<CanFdFrame>();
+        let write_rv = unsafe {
+            let tx_msg_ptr = tx_msg as *const TxMsgFd;
+            write(self.fd, tx_msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    /// FD-aware equivalent of `read_msg`. Use this to read back replies for
+    /// tasks set up via `send_cyclic_fd`/`filter_content_fd`, whose appended
+    /// frames are sized as `CanFdFrame`s rather than classic `CanFrame`s.
+    pub fn read_msg_fd(&self) -> io::Result<BcmMsgHeadFd> {
+        let ival1 = c_timeval_new(time::Duration::from_millis(0));
+        let ival2 = c_timeval_new(time::Duration::from_millis(0));
+        let frames = [CanFdFrame::new(0x0, &[], false).unwrap(); MAX_NFRAMES as usize];
+        let mut msg = BcmMsgHeadFd {
+            _opcode: 0,
+            _flags: 0,
+            _count: 0,
+            _ival1: ival1,
+            _ival2: ival2,
+            _can_id: 0,
+            _nframes: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _frames: frames,
+        };
+
+        let msg_ptr = &mut msg as *mut BcmMsgHeadFd;
+        let count = unsafe {
+            read(
+                self.fd.clone(),
+                msg_ptr as *mut c_void,
+                size_of::<BcmMsgHeadFd>(),
+            )
+        };
+
+        let last_error = io::Error::last_os_error();
+        if count < 0 { Err(last_error) } else { Ok(msg) }
+    }
+
+    /// Query the kernel for the classic (non-FD) cyclic transmission task
+    /// currently programmed for `can_id`, replying with its `TX_STATUS`
+    /// (current count, ival1, ival2, and frames). `eff` must match what the
+    /// task was set up with. If the task is CAN-FD, use `read_tx_task_fd`
+    /// instead.
+    pub fn read_tx_task(&self, can_id: c_uint, eff: bool) -> io::Result<BcmMsgHead> {
+        self.read_task(can_id, eff, TX_READ)
+    }
+
+    /// Query the kernel for the classic (non-FD) RX content filter currently
+    /// programmed for `can_id`, replying with its `RX_STATUS` (current
+    /// count, ival1, ival2, and frames). `eff` must match what the filter
+    /// was set up with. If the filter is CAN-FD, use `read_rx_filter_fd`
+    /// instead.
+    pub fn read_rx_filter(&self, can_id: c_uint, eff: bool) -> io::Result<BcmMsgHead> {
+        self.read_task(can_id, eff, RX_READ)
+    }
+
+    /// FD-aware equivalent of `read_tx_task`, for a task set up via
+    /// `send_cyclic_fd`.
+    pub fn read_tx_task_fd(&self, can_id: c_uint, eff: bool) -> io::Result<BcmMsgHeadFd> {
+        self.read_task_fd(can_id, eff, TX_READ)
+    }
+
+    /// FD-aware equivalent of `read_rx_filter`, for a filter set up via
+    /// `filter_content_fd`.
+    pub fn read_rx_filter_fd(&self, can_id: c_uint, eff: bool) -> io::Result<BcmMsgHeadFd> {
+        self.read_task_fd(can_id, eff, RX_READ)
+    }
+
+    fn read_task(&self, can_id: c_uint, eff: bool, opcode: u32) -> io::Result<BcmMsgHead> {
+        match self.read_task_raw(can_id, eff, opcode, 0)? {
+            BcmAnyMessage::Classic(msg) => Ok(match msg {
+                BcmMessage::TxStatus(head) |
+                BcmMessage::TxExpired(head) |
+                BcmMessage::RxStatus(head) |
+                BcmMessage::RxTimeout(head) |
+                BcmMessage::RxChanged(head) |
+                BcmMessage::Other(head) => head,
+            }),
+            BcmAnyMessage::Fd(_) => Err(Error::new(
+                ErrorKind::InvalidData,
+                "task is CAN-FD; use read_tx_task_fd/read_rx_filter_fd",
+            )),
+        }
+    }
+
+    fn read_task_fd(&self, can_id: c_uint, eff: bool, opcode: u32) -> io::Result<BcmMsgHeadFd> {
+        match self.read_task_raw(can_id, eff, opcode, CAN_FD_FRAME)? {
+            BcmAnyMessage::Fd(msg) => Ok(match msg {
+                BcmMessageFd::TxStatus(head) |
+                BcmMessageFd::TxExpired(head) |
+                BcmMessageFd::RxStatus(head) |
+                BcmMessageFd::RxTimeout(head) |
+                BcmMessageFd::RxChanged(head) |
+                BcmMessageFd::Other(head) => head,
+            }),
+            BcmAnyMessage::Classic(_) => Err(Error::new(
+                ErrorKind::InvalidData,
+                "task is classic (non-FD); use read_tx_task/read_rx_filter",
+            )),
+        }
+    }
+
+    /// Write a `TX_READ`/`RX_READ` request for `can_id` and return the
+    /// matching `TX_STATUS`/`RX_STATUS` reply, in whichever frame layout
+    /// (classic or CAN-FD) the kernel actually sends it in. `flags` is
+    /// `CAN_FD_FRAME` to address a CAN-FD task, or 0 for a classic one.
+    fn read_task_raw(&self, can_id: c_uint, eff: bool, opcode: u32, flags: u32) -> io::Result<BcmAnyMessage> {
+        let tagged_can_id = eff_can_id(can_id, eff);
+        let msg = &BcmMsgHeadFrameLess {
+            _opcode: opcode,
+            _flags: flags,
+            _count: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(time::Duration::new(0, 0)),
+            _ival2: c_timeval_new(time::Duration::new(0, 0)),
+            _can_id: tagged_can_id,
+            _nframes: 0,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>();
+        let write_rv = unsafe {
+            let msg_ptr = msg as *const BcmMsgHeadFrameLess;
+            write(self.fd, msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        // The fd may be shared with cyclic TX tasks, RX filters and one-shot
+        // sends, so an unrelated notification (TX_EXPIRED, RX_CHANGED, ...)
+        // can arrive before the status reply we just asked for. Skip past
+        // anything that isn't the matching TX_STATUS/RX_STATUS for this id.
+        // Messages are read via `read_any` rather than the classic-only
+        // `read_msg`, since a pending CAN_FD_FRAME notification would
+        // otherwise be truncated and misparsed by a fixed classic-size read.
+        let expected_opcode = match opcode {
+            TX_READ => TX_STATUS,
+            RX_READ => RX_STATUS,
+            other => other,
+        };
+
+        loop {
+            let any = self.read_any()?;
+            let matches = match any {
+                BcmAnyMessage::Classic(ref msg) => {
+                    msg.head().opcode() == expected_opcode && msg.can_id() == tagged_can_id
+                }
+                BcmAnyMessage::Fd(ref msg) => {
+                    msg.head().opcode() == expected_opcode && msg.can_id() == tagged_can_id
+                }
+            };
+
+            if matches {
+                return Ok(any);
+            }
+        }
+    }
+
+    /// Send a single frame through the BCM socket via `TX_SEND`, bypassing
+    /// any cyclic task setup. Useful for sharing one BCM fd for both cyclic
+    /// and sporadic traffic.
+    pub fn send_once(&self, frame: &CanFrame) -> io::Result<()> {
+        let frames = padded_frames(slice::from_ref(frame))?;
+
+        let msg = BcmMsgHeadFrameLess {
+            _opcode: TX_SEND,
+            _flags: 0,
+            _count: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _ival1: c_timeval_new(time::Duration::new(0, 0)),
+            _ival2: c_timeval_new(time::Duration::new(0, 0)),
+            _can_id: frame.can_id(),
+            _nframes: 1,
+        };
+
+        let tx_msg = &TxMsg {
+            _msg_head: msg,
+            _frames: frames,
+        };
+
+        let write_size = size_of::<BcmMsgHeadFrameLess>() + size_of::<CanFrame>();
+        let write_rv = unsafe {
+            let tx_msg_ptr = tx_msg as *const TxMsg;
+            write(self.fd, tx_msg_ptr as *const c_void, write_size)
+        };
+
+        if write_rv as usize != write_size {
+            return Err(Error::new(ErrorKind::WriteZero, io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
     /// Read a single can frame.
     pub fn read_msg(&self) -> io::Result<BcmMsgHead> {
 
@@ -317,6 +946,36 @@ impl CanBCMSocket {
         let last_error = io::Error::last_os_error();
         if count < 0 { Err(last_error) } else { Ok(msg) }
     }
+
+    /// Peek the `_flags` word of the next pending message without consuming
+    /// it, so the caller can tell whether it was posted by a `CAN_FD_FRAME`
+    /// task before picking the right read buffer layout.
+    fn peek_flags(&self) -> io::Result<u32> {
+        // `_opcode` then `_flags`, each a u32 - see `BcmMsgHead`.
+        let mut buf = [0u8; 8];
+        let n = unsafe {
+            recv(self.fd, buf.as_mut_ptr() as *mut c_void, buf.len(), MSG_PEEK)
+        };
+
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(u32::from_ne_bytes([buf[4], buf[5], buf[6], buf[7]]))
+    }
+
+    /// Read a single incoming broadcast-manager message, dispatching to the
+    /// classic or CAN FD frame layout based on the kernel's `CAN_FD_FRAME`
+    /// flag so replies from `send_cyclic_fd`/`filter_content_fd` tasks are
+    /// decoded with the right (72-byte) frame size instead of being
+    /// misparsed as classic (16-byte) frames.
+    pub fn read_any(&self) -> io::Result<BcmAnyMessage> {
+        if self.peek_flags()? & CAN_FD_FRAME != 0 {
+            self.read_msg_fd().map(|head| BcmAnyMessage::Fd(BcmMessageFd::from_head(head)))
+        } else {
+            self.read_msg().map(|head| BcmAnyMessage::Classic(BcmMessage::from_head(head)))
+        }
+    }
 }
 
 impl Evented for CanBCMSocket {
@@ -351,6 +1010,114 @@ impl Drop for CanBCMSocket {
     }
 }
 
+/// A decoded broadcast-manager notification, pairing its `kind()` with the
+/// `can_id` and frames the kernel reported. `BcmStream` yields it, wrapped in
+/// `BcmAnyMessage::Classic`, for classic tasks, so a consumer can drive an RX timeout-monitoring state machine by
+/// matching on the variant instead of picking apart a raw `BcmMsgHead`.
+#[derive(Debug)]
+pub enum BcmMessage {
+    TxStatus(BcmMsgHead),
+    TxExpired(BcmMsgHead),
+    RxStatus(BcmMsgHead),
+    RxTimeout(BcmMsgHead),
+    RxChanged(BcmMsgHead),
+    Other(BcmMsgHead),
+}
+
+impl BcmMessage {
+    fn from_head(head: BcmMsgHead) -> BcmMessage {
+        match head.kind() {
+            BcmOpcode::TxStatus => BcmMessage::TxStatus(head),
+            BcmOpcode::TxExpired => BcmMessage::TxExpired(head),
+            BcmOpcode::RxStatus => BcmMessage::RxStatus(head),
+            BcmOpcode::RxTimeout => BcmMessage::RxTimeout(head),
+            BcmOpcode::RxChanged => BcmMessage::RxChanged(head),
+            BcmOpcode::Other(_) => BcmMessage::Other(head),
+        }
+    }
+
+    /// The `can_id` the notification pertains to.
+    pub fn can_id(&self) -> u32 {
+        self.head().can_id()
+    }
+
+    /// The frames the kernel reported alongside this notification, if any.
+    pub fn frames(&self) -> &[CanFrame] {
+        self.head().frames()
+    }
+
+    /// The underlying `BcmMsgHead`, for access to `count()`/`ival1()`/`ival2()`.
+    pub fn head(&self) -> &BcmMsgHead {
+        match *self {
+            BcmMessage::TxStatus(ref head) |
+            BcmMessage::TxExpired(ref head) |
+            BcmMessage::RxStatus(ref head) |
+            BcmMessage::RxTimeout(ref head) |
+            BcmMessage::RxChanged(ref head) |
+            BcmMessage::Other(ref head) => head,
+        }
+    }
+}
+
+/// FD-aware equivalent of `BcmMessage`, pairing `kind()` with the `can_id`
+/// and `CanFdFrame`s the kernel reported for a `CAN_FD_FRAME`-flagged task.
+#[derive(Debug)]
+pub enum BcmMessageFd {
+    TxStatus(BcmMsgHeadFd),
+    TxExpired(BcmMsgHeadFd),
+    RxStatus(BcmMsgHeadFd),
+    RxTimeout(BcmMsgHeadFd),
+    RxChanged(BcmMsgHeadFd),
+    Other(BcmMsgHeadFd),
+}
+
+impl BcmMessageFd {
+    fn from_head(head: BcmMsgHeadFd) -> BcmMessageFd {
+        match head.kind() {
+            BcmOpcode::TxStatus => BcmMessageFd::TxStatus(head),
+            BcmOpcode::TxExpired => BcmMessageFd::TxExpired(head),
+            BcmOpcode::RxStatus => BcmMessageFd::RxStatus(head),
+            BcmOpcode::RxTimeout => BcmMessageFd::RxTimeout(head),
+            BcmOpcode::RxChanged => BcmMessageFd::RxChanged(head),
+            BcmOpcode::Other(_) => BcmMessageFd::Other(head),
+        }
+    }
+
+    /// The `can_id` the notification pertains to.
+    pub fn can_id(&self) -> u32 {
+        self.head().can_id()
+    }
+
+    /// The frames the kernel reported alongside this notification, if any.
+    pub fn frames(&self) -> &[CanFdFrame] {
+        self.head().frames()
+    }
+
+    /// The underlying `BcmMsgHeadFd`, for access to `count()`/`ival1()`/`ival2()`.
+    pub fn head(&self) -> &BcmMsgHeadFd {
+        match *self {
+            BcmMessageFd::TxStatus(ref head) |
+            BcmMessageFd::TxExpired(ref head) |
+            BcmMessageFd::RxStatus(ref head) |
+            BcmMessageFd::RxTimeout(ref head) |
+            BcmMessageFd::RxChanged(ref head) |
+            BcmMessageFd::Other(ref head) => head,
+        }
+    }
+}
+
+/// A decoded broadcast-manager notification of either frame layout. Classic
+/// tasks (`send_cyclic`/`filter_content`) yield `Classic`; CAN FD tasks
+/// (`send_cyclic_fd`/`filter_content_fd`) yield `Fd`. `BcmStream` picks the
+/// right variant per message via the kernel's `CAN_FD_FRAME` flag, so a
+/// single fd can multiplex both without either corrupting the other's
+/// frames.
+#[derive(Debug)]
+pub enum BcmAnyMessage {
+    Classic(BcmMessage),
+    Fd(BcmMessageFd),
+}
+
 pub struct BcmStream {
     io: PollEvented<CanBCMSocket>,
 }
@@ -370,14 +1137,14 @@ impl BcmStream {
 }
 
 impl futures::stream::Stream for BcmStream {
-    type Item = BcmMsgHead;
+    type Item = BcmAnyMessage;
     type Error = io::Error;
     fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
         if let futures::Async::NotReady = self.io.poll_read() {
             return Ok(futures::Async::NotReady);
         }
 
-        match self.io.get_ref().read_msg() {
+        match self.io.get_ref().read_any() {
             Ok(n) => Ok(futures::Async::Ready(Some(n))),
             Err(e) => {
                 if e.kind() == io::ErrorKind::WouldBlock {
@@ -388,4 +1155,100 @@ impl futures::stream::Stream for BcmStream {
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(opcode: u32, can_id: u32) -> BcmMsgHead {
+        BcmMsgHead {
+            _opcode: opcode,
+            _flags: 0,
+            _count: 0,
+            _ival1: c_timeval_new(time::Duration::new(0, 0)),
+            _ival2: c_timeval_new(time::Duration::new(0, 0)),
+            _can_id: can_id,
+            _nframes: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _frames: [CanFrame::new(0x0, &[], false, false).unwrap(); MAX_NFRAMES as usize],
+        }
+    }
+
+    fn fd_head(opcode: u32, can_id: u32) -> BcmMsgHeadFd {
+        BcmMsgHeadFd {
+            _opcode: opcode,
+            _flags: CAN_FD_FRAME,
+            _count: 0,
+            _ival1: c_timeval_new(time::Duration::new(0, 0)),
+            _ival2: c_timeval_new(time::Duration::new(0, 0)),
+            _can_id: can_id,
+            _nframes: 0,
+            #[cfg(all(target_pointer_width = "32"))]
+            _pad: 0,
+            _frames: [CanFdFrame::new(0x0, &[], false).unwrap(); MAX_NFRAMES as usize],
+        }
+    }
+
+    #[test]
+    fn eff_can_id_tags_extended_ids_only() {
+        assert_eq!(eff_can_id(0x123, false), 0x123);
+        assert_eq!(eff_can_id(0x123, true), 0x123 | EFF_FLAG);
+    }
+
+    #[test]
+    fn padded_frames_rejects_too_many_frames() {
+        let frame = CanFrame::new(0x1, &[], false, false).unwrap();
+        let frames = vec![frame; MAX_NFRAMES as usize + 1];
+        assert!(padded_frames(&frames).is_err());
+    }
+
+    #[test]
+    fn padded_frames_copies_supplied_frames() {
+        let frame = CanFrame::new(0x42, &[1, 2, 3], false, false).unwrap();
+        let buf = padded_frames(&[frame]).unwrap();
+        assert_eq!(buf[0].can_id(), 0x42);
+    }
+
+    #[test]
+    fn padded_fd_frames_rejects_too_many_frames() {
+        let frame = CanFdFrame::new(0x1, &[], false).unwrap();
+        let frames = vec![frame; MAX_NFRAMES as usize + 1];
+        assert!(padded_fd_frames(&frames).is_err());
+    }
+
+    #[test]
+    fn padded_fd_frames_copies_supplied_frames() {
+        let frame = CanFdFrame::new(0x42, &[1, 2, 3, 4], true).unwrap();
+        let buf = padded_fd_frames(&[frame]).unwrap();
+        assert_eq!(buf[0].can_id(), 0x42 | EFF_FLAG);
+        assert_eq!(buf[0].data(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn bcm_opcode_decodes_known_opcodes() {
+        assert_eq!(BcmOpcode::from_raw(TX_STATUS), BcmOpcode::TxStatus);
+        assert_eq!(BcmOpcode::from_raw(TX_EXPIRED), BcmOpcode::TxExpired);
+        assert_eq!(BcmOpcode::from_raw(RX_STATUS), BcmOpcode::RxStatus);
+        assert_eq!(BcmOpcode::from_raw(RX_TIMEOUT), BcmOpcode::RxTimeout);
+        assert_eq!(BcmOpcode::from_raw(RX_CHANGED), BcmOpcode::RxChanged);
+        assert_eq!(BcmOpcode::from_raw(0xffff), BcmOpcode::Other(0xffff));
+    }
+
+    #[test]
+    fn bcm_message_from_head_maps_variant_and_preserves_can_id() {
+        match BcmMessage::from_head(head(RX_CHANGED, 0x77)) {
+            BcmMessage::RxChanged(head) => assert_eq!(head.can_id(), 0x77),
+            other => panic!("expected RxChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bcm_message_fd_from_head_maps_variant_and_preserves_can_id() {
+        match BcmMessageFd::from_head(fd_head(TX_STATUS, 0x88)) {
+            BcmMessageFd::TxStatus(head) => assert_eq!(head.can_id(), 0x88),
+            other => panic!("expected TxStatus, got {:?}", other),
+        }
+    }
 }
\ No newline at end of file